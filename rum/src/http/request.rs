@@ -12,6 +12,85 @@ use tokio::io::{AsyncRead, AsyncReadExt};
 
 use super::{Cookies, Error, Head, Params, ToParameter};
 
+/// `Content-Encoding` of a request body.
+///
+/// Marked `#[non_exhaustive]` since more codecs may be supported later;
+/// callers must handle unknown values rather than matching exhaustively.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+    Br,
+    Identity,
+}
+
+impl ContentEncoding {
+    fn from_header(value: &str) -> Option<Self> {
+        // Content-Encoding tokens are case-insensitive (RFC 7231 3.1.2.2).
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentEncoding::Gzip),
+            "deflate" => Some(ContentEncoding::Deflate),
+            "br" => Some(ContentEncoding::Br),
+            "identity" | "" => Some(ContentEncoding::Identity),
+            _ => None,
+        }
+    }
+}
+
+/// Limits applied while decoding a compressed request body.
+#[derive(Debug, Clone, Copy)]
+pub struct DecompressionOptions {
+    /// Maximum number of bytes a compressed body may expand to.
+    ///
+    /// Guards against decompression bombs, where a small compressed
+    /// body expands to an unreasonable amount of memory.
+    pub max_decompressed_size: usize,
+}
+
+impl Default for DecompressionOptions {
+    fn default() -> Self {
+        Self {
+            max_decompressed_size: 10 * 1024 * 1024, // 10MB
+        }
+    }
+}
+
+/// A [`std::io::Write`] sink that stops accepting bytes once `limit` would be
+/// exceeded, so a decompressor writing into it can be cut off mid-stream
+/// instead of being left to inflate an unbounded decompression bomb.
+struct BoundedWriter<'a> {
+    out: &'a mut Vec<u8>,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl<'a> BoundedWriter<'a> {
+    fn new(out: &'a mut Vec<u8>, limit: usize) -> Self {
+        Self {
+            out,
+            limit,
+            exceeded: false,
+        }
+    }
+}
+
+impl std::io::Write for BoundedWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.out.len() + data.len() > self.limit {
+            self.exceeded = true;
+            return Err(std::io::Error::other("decompressed body exceeds limit"));
+        }
+
+        self.out.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// HTTP request.
 ///
 /// The request is fully loaded into memory. It's safe to clone
@@ -29,17 +108,54 @@ struct Inner {
     cookies: Cookies,
 }
 
+/// Default `max_body_size` used by [`Request::read`] and
+/// [`Request::read_with_decompression`].
+const DEFAULT_MAX_BODY_SIZE: usize = 10 * 1024 * 1024; // 10MB
+
 impl Request {
     /// Read the request in its entirety from a stream.
-    pub async fn read(mut stream: impl AsyncRead + Unpin) -> Result<Self, Error> {
+    pub async fn read(stream: impl AsyncRead + Unpin) -> Result<Self, Error> {
+        Self::read_with_limits(stream, DEFAULT_MAX_BODY_SIZE, DecompressionOptions::default()).await
+    }
+
+    /// Read the request in its entirety from a stream, applying the given
+    /// [`DecompressionOptions`] when the body arrives with a `Content-Encoding`.
+    pub async fn read_with_decompression(
+        stream: impl AsyncRead + Unpin,
+        decompression: DecompressionOptions,
+    ) -> Result<Self, Error> {
+        Self::read_with_limits(stream, DEFAULT_MAX_BODY_SIZE, decompression).await
+    }
+
+    /// Read the request in its entirety from a stream, rejecting it upfront
+    /// with [`Error::PayloadTooLarge`] if `Content-Length` exceeds `max_body_size`.
+    pub async fn read_with_limits(
+        mut stream: impl AsyncRead + Unpin,
+        max_body_size: usize,
+        decompression: DecompressionOptions,
+    ) -> Result<Self, Error> {
         let head = Head::read(&mut stream).await?;
         let content_length = head.content_length().unwrap_or(0);
+        if content_length > max_body_size {
+            return Err(Error::PayloadTooLarge);
+        }
+
         let mut body = vec![0u8; content_length];
         stream
             .read_exact(&mut body)
             .await
             .map_err(|_| Error::MalformedRequest("incorrect content length"))?;
 
+        let body = match head.content_encoding() {
+            Some(value) => {
+                let encoding = ContentEncoding::from_header(value)
+                    .ok_or(Error::UnsupportedEncoding)?;
+                Self::decompress(encoding, &body, &decompression)?
+            }
+
+            None => body,
+        };
+
         Ok(Request {
             params: None,
             inner: Arc::new(Inner {
@@ -50,6 +166,80 @@ impl Request {
         })
     }
 
+    /// Read only the request head from a stream, leaving the body unread.
+    ///
+    /// Returns the parsed [`Head`] alongside an [`AsyncRead`] positioned at
+    /// the start of the body, capped to `Content-Length` bytes. Unlike
+    /// [`Request::read`], nothing is copied into an owned buffer here, so
+    /// the caller drives how (and how much of) the body gets consumed.
+    pub async fn read_streaming<S>(mut stream: S) -> Result<(Head, impl AsyncRead + Unpin), Error>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let head = Head::read(&mut stream).await?;
+        let content_length = head.content_length().unwrap_or(0) as u64;
+
+        Ok((head, stream.take(content_length)))
+    }
+
+    fn decompress(
+        encoding: ContentEncoding,
+        body: &[u8],
+        options: &DecompressionOptions,
+    ) -> Result<Vec<u8>, Error> {
+        if encoding == ContentEncoding::Identity {
+            return Ok(body.to_vec());
+        }
+
+        let mut out = Vec::new();
+        let mut sink = BoundedWriter::new(&mut out, options.max_decompressed_size);
+
+        let result: std::io::Result<()> = match encoding {
+            ContentEncoding::Identity => unreachable!(),
+
+            ContentEncoding::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    let mut decoder = flate2::read::GzDecoder::new(body);
+                    std::io::copy(&mut decoder, &mut sink).map(|_| ())
+                }
+
+                #[cfg(not(feature = "gzip"))]
+                return Err(Error::UnsupportedEncoding);
+            }
+
+            ContentEncoding::Deflate => {
+                #[cfg(feature = "deflate")]
+                {
+                    let mut decoder = flate2::read::DeflateDecoder::new(body);
+                    std::io::copy(&mut decoder, &mut sink).map(|_| ())
+                }
+
+                #[cfg(not(feature = "deflate"))]
+                return Err(Error::UnsupportedEncoding);
+            }
+
+            ContentEncoding::Br => {
+                #[cfg(feature = "brotli")]
+                {
+                    brotli::BrotliDecompress(&mut &body[..], &mut sink)
+                }
+
+                #[cfg(not(feature = "brotli"))]
+                return Err(Error::UnsupportedEncoding);
+            }
+        };
+
+        let exceeded = sink.exceeded;
+
+        if exceeded {
+            return Err(Error::PayloadTooLarge);
+        }
+        result.map_err(|_| Error::MalformedRequest("invalid compressed body"))?;
+
+        Ok(out)
+    }
+
     pub fn with_params(mut self, params: Arc<Params>) -> Self {
         self.params = Some(params);
         self
@@ -79,9 +269,37 @@ impl Request {
     }
 
     /// Request's body as HTML.
-    /// UTF-8 encoding is assumed, and all incompatible characters are dropped.
-    pub fn html(&self) -> String {
-        String::from_utf8_lossy(self.body()).to_string()
+    ///
+    /// Decoded using the charset declared in the `Content-Type` header,
+    /// see [`Request::text`].
+    pub fn html(&self) -> Result<String, Error> {
+        self.text()
+    }
+
+    /// Request's body decoded as text.
+    ///
+    /// Looks up the `charset` parameter of the `Content-Type` header and
+    /// decodes the body with that encoding, defaulting to UTF-8 when none
+    /// is given. An unrecognized charset label is an error rather than a
+    /// best-effort guess.
+    pub fn text(&self) -> Result<String, Error> {
+        let encoding = match self.content_type().and_then(Self::charset) {
+            Some(charset) => encoding_rs::Encoding::for_label(charset.as_bytes())
+                .ok_or(Error::MalformedRequest("unknown charset"))?,
+            None => encoding_rs::UTF_8,
+        };
+
+        let (text, _, _) = encoding.decode(self.body());
+        Ok(text.into_owned())
+    }
+
+    /// Extract the `charset` parameter from a `Content-Type` header value.
+    fn charset(content_type: &str) -> Option<&str> {
+        content_type.split(';').skip(1).find_map(|param| {
+            let (name, value) = param.trim().split_once('=')?;
+            name.eq_ignore_ascii_case("charset")
+                .then(|| value.trim_matches('"'))
+        })
     }
 
     /// Request's body deserialized from JSON into a particular Rust type.
@@ -90,6 +308,41 @@ impl Request {
         T::deserialize(&mut deserializer)
     }
 
+    /// Request's body as a stream of newline-delimited JSON (JSON Lines / NDJSON) documents.
+    ///
+    /// Built on [`serde_json::Deserializer::into_iter`], which already treats
+    /// whitespace between values as a separator, each document is
+    /// deserialized lazily and borrows directly from the request body, so
+    /// deserialized types may borrow from `'a`. Use [`Request::json`] when
+    /// the body holds a single value instead.
+    pub fn json_lines<'a, T: Deserialize<'a> + 'a>(
+        &'a self,
+    ) -> impl Iterator<Item = Result<T, serde_json::Error>> + 'a {
+        Deserializer::from_slice(self.body()).into_iter::<T>()
+    }
+
+    /// Request's body deserialized from a URL-encoded form into a particular Rust type.
+    ///
+    /// Rejects the request with an error if the `Content-Type` header isn't
+    /// `application/x-www-form-urlencoded`, rather than guessing at the body's shape.
+    pub fn form<'a, T: Deserialize<'a>>(&'a self) -> Result<T, Error> {
+        let media_type = self
+            .content_type()
+            .and_then(|content_type| content_type.split(';').next())
+            .map(|media_type| media_type.trim());
+
+        match media_type {
+            Some(media_type) if media_type.eq_ignore_ascii_case("application/x-www-form-urlencoded") => {
+                serde_urlencoded::from_bytes(self.body())
+                    .map_err(|_| Error::MalformedRequest("invalid form body"))
+            }
+
+            _ => Err(Error::MalformedRequest(
+                "expected content type application/x-www-form-urlencoded",
+            )),
+        }
+    }
+
     /// Request's cookies.
     pub fn cookies(&self) -> &Cookies {
         &self.inner.cookies
@@ -127,4 +380,251 @@ mod test {
         let json = response.json::<Hello>().expect("deserialize body");
         assert_eq!(json.hello, "world");
     }
+
+    #[tokio::test]
+    async fn test_form() {
+        #[derive(Deserialize)]
+        struct Hello {
+            hello: String,
+        }
+
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/x-www-form-urlencoded\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 11\r\n"
+            + "\r\n"
+            + "hello=world")
+            .as_bytes()
+            .to_vec();
+        let request = Request::read(&body[..]).await.expect("request");
+        let form = request.form::<Hello>().expect("deserialize body");
+        assert_eq!(form.hello, "world");
+    }
+
+    #[tokio::test]
+    async fn test_form_content_type_with_charset_parameter() {
+        #[derive(Deserialize)]
+        struct Hello {
+            hello: String,
+        }
+
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: Application/X-WWW-Form-Urlencoded; charset=utf-8\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 11\r\n"
+            + "\r\n"
+            + "hello=world")
+            .as_bytes()
+            .to_vec();
+        let request = Request::read(&body[..]).await.expect("request");
+        let form = request.form::<Hello>().expect("deserialize body");
+        assert_eq!(form.hello, "world");
+    }
+
+    #[tokio::test]
+    async fn test_form_rejects_lookalike_content_type() {
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/x-www-form-urlencodedXYZ\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 11\r\n"
+            + "\r\n"
+            + "hello=world")
+            .as_bytes()
+            .to_vec();
+        let request = Request::read(&body[..]).await.expect("request");
+        assert!(request.form::<serde_json::Value>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_text_default_utf8() {
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: text/plain\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 5\r\n"
+            + "\r\n"
+            + "hello")
+            .as_bytes()
+            .to_vec();
+        let request = Request::read(&body[..]).await.expect("request");
+        assert_eq!(request.text().expect("decode"), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_text_latin1_charset() {
+        let head = "GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: text/plain; charset=iso-8859-1\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 1\r\n"
+            + "\r\n";
+        let mut body = head.as_bytes().to_vec();
+        body.push(0xE9); // 'é' in ISO-8859-1
+
+        let request = Request::read(&body[..]).await.expect("request");
+        assert_eq!(request.text().expect("decode"), "é");
+    }
+
+    #[tokio::test]
+    async fn test_json_lines() {
+        #[derive(Deserialize)]
+        struct Hello {
+            hello: String,
+        }
+
+        let json = "{\"hello\": \"world\"}\n{\"hello\": \"rust\"}";
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/x-ndjson\r\n"
+            + "Accept: */*\r\n"
+            + &format!("Content-Length: {}\r\n", json.len())
+            + "\r\n"
+            + json)
+            .as_bytes()
+            .to_vec();
+        let request = Request::read(&body[..]).await.expect("request");
+        let lines: Vec<Hello> = request
+            .json_lines::<Hello>()
+            .collect::<Result<_, _>>()
+            .expect("deserialize body");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].hello, "world");
+        assert_eq!(lines[1].hello, "rust");
+    }
+
+    #[tokio::test]
+    async fn test_read_with_limits_payload_too_large() {
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/json\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 18\r\n"
+            + "\r\n"
+            + r#"{"hello": "world"}"#)
+            .as_bytes()
+            .to_vec();
+        let result = Request::read_with_limits(&body[..], 4, DecompressionOptions::default()).await;
+        assert!(matches!(result, Err(Error::PayloadTooLarge)));
+    }
+
+    #[tokio::test]
+    async fn test_read_streaming() {
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/json\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 18\r\n"
+            + "\r\n"
+            + r#"{"hello": "world"}"#)
+            .as_bytes()
+            .to_vec();
+        let (_head, mut stream) = Request::read_streaming(&body[..]).await.expect("head");
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.expect("read body");
+        assert_eq!(buf, br#"{"hello": "world"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_form_wrong_content_type() {
+        let body = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/json\r\n"
+            + "Accept: */*\r\n"
+            + "Content-Length: 18\r\n"
+            + "\r\n"
+            + r#"{"hello": "world"}"#)
+            .as_bytes()
+            .to_vec();
+        let request = Request::read(&body[..]).await.expect("request");
+        assert!(request.form::<serde_json::Value>().is_err());
+    }
+
+    fn request_with_encoding(encoding: &str, body: Vec<u8>) -> Vec<u8> {
+        let mut request = ("GET / HTTP/1.1\r\n".to_owned()
+            + "Content-Type: application/json\r\n"
+            + &format!("Content-Encoding: {encoding}\r\n")
+            + "Accept: */*\r\n"
+            + &format!("Content-Length: {}\r\n", body.len())
+            + "\r\n")
+            .into_bytes();
+        request.extend_from_slice(&body);
+        request
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_read_gzip_body() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(br#"{"hello": "world"}"#).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let request = Request::read(&request_with_encoding("gzip", compressed)[..])
+            .await
+            .expect("request");
+        assert_eq!(request.body(), br#"{"hello": "world"}"#);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_read_gzip_body_case_insensitive_header() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(br#"{"hello": "world"}"#).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let request = Request::read(&request_with_encoding("GZIP", compressed)[..])
+            .await
+            .expect("request");
+        assert_eq!(request.body(), br#"{"hello": "world"}"#);
+    }
+
+    #[cfg(feature = "deflate")]
+    #[tokio::test]
+    async fn test_read_deflate_body() {
+        use std::io::Write;
+
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(br#"{"hello": "world"}"#).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let request = Request::read(&request_with_encoding("deflate", compressed)[..])
+            .await
+            .expect("request");
+        assert_eq!(request.body(), br#"{"hello": "world"}"#);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[tokio::test]
+    async fn test_read_brotli_body() {
+        let mut compressed = Vec::new();
+        let params = brotli::enc::BrotliEncoderParams::default();
+        brotli::BrotliCompress(
+            &mut &br#"{"hello": "world"}"#[..],
+            &mut compressed,
+            &params,
+        )
+        .unwrap();
+
+        let request = Request::read(&request_with_encoding("br", compressed)[..])
+            .await
+            .expect("request");
+        assert_eq!(request.body(), br#"{"hello": "world"}"#);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_read_rejects_decompression_bomb() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&vec![0u8; 50 * 1024 * 1024]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let result = Request::read_with_decompression(
+            &request_with_encoding("gzip", compressed)[..],
+            DecompressionOptions {
+                max_decompressed_size: 1024,
+            },
+        )
+        .await;
+        assert!(matches!(result, Err(Error::PayloadTooLarge)));
+    }
 }
@@ -1,19 +1,52 @@
 use super::{Cron, Error, Job, JobHandler};
 use crate::colors::MaybeColorize;
 
-use std::sync::Arc;
-use time::OffsetDateTime;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use time::{Duration as TimeDuration, OffsetDateTime};
 
 use tokio::time::{interval, Duration};
-use tracing::error;
+use tracing::{error, warn};
+
+/// How far back `Clock::run` will look for missed ticks, e.g. after the
+/// process was suspended or fell behind. Bounds the catch-up so a long
+/// pause can't replay an unbounded backlog of slots.
+const DEFAULT_MAX_LOOKBACK: TimeDuration = TimeDuration::minutes(5);
 
 pub struct ScheduledJob {
     job: Box<JobHandler>,
     args: serde_json::Value,
     cron: Cron,
+    allow_overlap: bool,
+    running: Exclusivity,
+    last_evaluated: Mutex<Option<OffsetDateTime>>,
 }
 
 impl ScheduledJob {
+    pub fn new(job: Box<JobHandler>, args: serde_json::Value, cron: Cron) -> Self {
+        Self {
+            job,
+            args,
+            cron,
+            allow_overlap: true,
+            running: Exclusivity::default(),
+            last_evaluated: Mutex::new(None),
+        }
+    }
+
+    /// When `false`, a firing of this job that's still running when its next
+    /// slot matches is skipped instead of started concurrently.
+    pub fn allow_overlap(mut self, allow_overlap: bool) -> Self {
+        self.allow_overlap = allow_overlap;
+        self
+    }
+
+    /// Shorthand for `allow_overlap(false)`: only one run of this job is
+    /// ever in flight at a time.
+    pub fn run_exclusive(self) -> Self {
+        self.allow_overlap(false)
+    }
+
     pub async fn schedule(&self) -> Result<(), Error> {
         self.job.job.execute_async(self.args.clone()).await?;
 
@@ -27,13 +60,139 @@ impl ScheduledJob {
     pub fn job(&self) -> &Box<dyn Job> {
         &self.job.job
     }
+
+    /// Every whole second in `(last_evaluated, now]` whose cron matches,
+    /// capped to `max_lookback` seconds before `now`. Advances
+    /// `last_evaluated` as a side effect, so each slot is only ever
+    /// returned once.
+    ///
+    /// `Clock::run` hands each tick's `now` to a freshly spawned task, so
+    /// calls here can arrive out of order if an older tick's task happens
+    /// to run after a newer one's. `last_evaluated` only ever moves
+    /// forward to guard against that: a late-arriving older `now` is
+    /// recognized as stale and contributes no slots, instead of rewinding
+    /// the watermark and making the next call re-fire slots that already ran.
+    fn due_slots(&self, now: OffsetDateTime, max_lookback: TimeDuration) -> Vec<OffsetDateTime> {
+        let mut last_evaluated = self.last_evaluated.lock().expect("lock poisoned");
+        record_due_slots(&mut last_evaluated, now, max_lookback, |slot| {
+            self.should_run(slot)
+        })
+    }
+
+    /// Claims exclusive access to run this job, returning `None` without
+    /// claiming it if `allow_overlap` is disabled and a run is already
+    /// in flight. The returned guard releases the claim when dropped,
+    /// whether `schedule` returns normally or its future panics.
+    fn begin_run(&self) -> Option<RunGuard<'_>> {
+        if self.allow_overlap {
+            return Some(RunGuard { running: None });
+        }
+
+        if self.running.flag.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(RunGuard {
+                running: Some(&self.running.flag),
+            })
+        }
+    }
+}
+
+/// Every whole second in `(last_evaluated, now]` for which `should_run`
+/// matches, capped to `max_lookback` seconds before `now` so a process
+/// that was asleep a long time doesn't replay an unbounded backlog.
+/// `last_evaluated: None` (no prior tick) is treated as "only check `now`
+/// itself".
+fn due_slots_since(
+    last_evaluated: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+    max_lookback: TimeDuration,
+    should_run: impl Fn(&OffsetDateTime) -> bool,
+) -> Vec<OffsetDateTime> {
+    let start = match last_evaluated {
+        // Never reach back further than `max_lookback`.
+        Some(last) => last.max(now - max_lookback),
+        // Don't replay history from before the clock started.
+        None => now - TimeDuration::SECOND,
+    };
+
+    let mut due = Vec::new();
+    let mut slot = start + TimeDuration::SECOND;
+    while slot <= now {
+        if should_run(&slot) {
+            due.push(slot);
+        }
+        slot += TimeDuration::SECOND;
+    }
+
+    due
+}
+
+/// Computes the slots due for `now` against a `last_evaluated` watermark,
+/// then advances the watermark, all as one step so a stale, out-of-order
+/// `now` can't be allowed to rewind it between the two.
+fn record_due_slots(
+    last_evaluated: &mut Option<OffsetDateTime>,
+    now: OffsetDateTime,
+    max_lookback: TimeDuration,
+    should_run: impl Fn(&OffsetDateTime) -> bool,
+) -> Vec<OffsetDateTime> {
+    let previous = *last_evaluated;
+    *last_evaluated = Some(advance_last_evaluated(previous, now));
+    due_slots_since(previous, now, max_lookback, should_run)
+}
+
+/// The watermark's next value given a tick's `now`. Only ever moves
+/// forward: a `now` that's no later than what's already stored is treated
+/// as a stale, out-of-order tick and leaves the watermark untouched.
+fn advance_last_evaluated(previous: Option<OffsetDateTime>, now: OffsetDateTime) -> OffsetDateTime {
+    match previous {
+        Some(last) if last >= now => last,
+        _ => now,
+    }
+}
+
+/// Tracks whether an exclusive (`!allow_overlap`) job currently has a run
+/// in flight. Unused when `allow_overlap` is enabled.
+#[derive(Default)]
+struct Exclusivity {
+    flag: AtomicBool,
+}
+
+/// Releases the [`Exclusivity`] claim it was handed when dropped, so a
+/// panicking job can't leave `running` stuck `true` forever. `None` means
+/// the job allows overlap and there's nothing to release.
+struct RunGuard<'a> {
+    running: Option<&'a AtomicBool>,
+}
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(running) = self.running {
+            running.store(false, Ordering::SeqCst);
+        }
+    }
 }
 
 pub struct Clock {
     jobs: Arc<Vec<ScheduledJob>>,
+    max_lookback: TimeDuration,
 }
 
 impl Clock {
+    pub fn new(jobs: Vec<ScheduledJob>) -> Self {
+        Self {
+            jobs: Arc::new(jobs),
+            max_lookback: DEFAULT_MAX_LOOKBACK,
+        }
+    }
+
+    /// Override how far back missed ticks are caught up. See [`ScheduledJob::due_slots`].
+    pub fn max_lookback(mut self, max_lookback: TimeDuration) -> Self {
+        self.max_lookback = max_lookback;
+        self
+    }
+
     pub async fn run(&self) {
         let mut clock = interval(Duration::from_secs(1));
 
@@ -41,10 +200,23 @@ impl Clock {
             clock.tick().await;
             let now = OffsetDateTime::now_utc();
             let jobs = self.jobs.clone();
+            let max_lookback = self.max_lookback;
 
             tokio::spawn(async move {
                 for job in jobs.iter() {
-                    if job.should_run(&now) {
+                    for slot in job.due_slots(now, max_lookback) {
+                        let guard = match job.begin_run() {
+                            Some(guard) => guard,
+                            None => {
+                                warn!(
+                                    "job {} is still running, skipping slot at {}",
+                                    job.job().job_name().green(),
+                                    slot
+                                );
+                                continue;
+                            }
+                        };
+
                         match job.schedule().await {
                             Ok(_) => (),
                             Err(err) => {
@@ -55,9 +227,116 @@ impl Clock {
                                 );
                             }
                         }
+
+                        drop(guard);
                     }
                 }
             });
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secs(n: i64) -> OffsetDateTime {
+        OffsetDateTime::UNIX_EPOCH + TimeDuration::seconds(n)
+    }
+
+    #[test]
+    fn due_slots_since_first_tick_only_checks_now() {
+        let due = due_slots_since(None, secs(100), TimeDuration::minutes(5), |_| true);
+        assert_eq!(due, vec![secs(100)]);
+    }
+
+    #[test]
+    fn due_slots_since_catches_up_gap() {
+        let due = due_slots_since(Some(secs(100)), secs(103), TimeDuration::minutes(5), |_| true);
+        assert_eq!(due, vec![secs(101), secs(102), secs(103)]);
+    }
+
+    #[test]
+    fn due_slots_since_filters_non_matching_slots() {
+        let due = due_slots_since(Some(secs(100)), secs(103), TimeDuration::minutes(5), |slot| {
+            *slot == secs(102)
+        });
+        assert_eq!(due, vec![secs(102)]);
+    }
+
+    #[test]
+    fn due_slots_since_caps_lookback() {
+        let due = due_slots_since(Some(secs(0)), secs(100), TimeDuration::seconds(2), |_| true);
+        assert_eq!(due, vec![secs(99), secs(100)]);
+    }
+
+    #[test]
+    fn due_slots_bookkeeping_ignores_a_stale_out_of_order_tick() {
+        let mut last_evaluated = None;
+
+        let first = record_due_slots(&mut last_evaluated, secs(101), TimeDuration::minutes(5), |_| true);
+        assert_eq!(first, vec![secs(101)]);
+
+        // An older tick's spawned task happens to finish after a newer one's.
+        let second = record_due_slots(&mut last_evaluated, secs(100), TimeDuration::minutes(5), |_| true);
+        assert_eq!(
+            second,
+            Vec::<OffsetDateTime>::new(),
+            "a stale tick must not fire anything"
+        );
+
+        let third = record_due_slots(&mut last_evaluated, secs(102), TimeDuration::minutes(5), |_| true);
+        assert_eq!(
+            third,
+            vec![secs(102)],
+            "slot 101 already fired and must not be replayed"
+        );
+    }
+
+    #[test]
+    fn run_guard_blocks_while_held_then_releases_on_drop() {
+        let running = Exclusivity::default();
+
+        fn claim(running: &Exclusivity) -> Option<RunGuard<'_>> {
+            if running.flag.swap(true, Ordering::SeqCst) {
+                None
+            } else {
+                Some(RunGuard {
+                    running: Some(&running.flag),
+                })
+            }
+        }
+
+        let first = claim(&running);
+        assert!(first.is_some(), "first claim should succeed");
+        assert!(
+            claim(&running).is_none(),
+            "second claim should be blocked while the first is held"
+        );
+
+        drop(first);
+        assert!(
+            claim(&running).is_some(),
+            "dropping the guard should release the claim"
+        );
+    }
+
+    #[test]
+    fn run_guard_releases_on_unwind() {
+        let running = Exclusivity::default();
+        running.flag.store(true, Ordering::SeqCst);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = RunGuard {
+                running: Some(&running.flag),
+            };
+            panic!("simulated job panic");
+        }));
+
+        assert!(result.is_err());
+        assert!(
+            !running.flag.load(Ordering::SeqCst),
+            "guard must release the claim even when its scope unwinds"
+        );
+    }
+}